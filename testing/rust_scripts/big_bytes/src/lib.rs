@@ -1,26 +1,69 @@
-extern {
-    fn println(ptr: *const u8, len: usize);
-}
+// `host_logger`, `panic_hook` and `guest_alloc` are identical in the `hello` and
+// `big_bytes` plugin crates (there's no shared Cargo crate to hang them off), so they live
+// once under `common/` and each of those crates points its own module at that file instead of keeping
+// a byte-for-byte copy that would drift.
+#[path = "../../common/src/alloc.rs"]
+mod guest_alloc;
+#[path = "../../common/src/host_logger.rs"]
+mod host_logger;
+#[path = "../../common/src/panic_hook.rs"]
+mod panic_hook;
+
+use guest_alloc::wr_alloc;
+use host_logger::println;
 
-#[no_mangle]
-pub extern "C" fn read_big_bytes(ptr: *mut u8, len: usize, output: &mut (*const u8, usize))  {
+// Copies `bytes` into a freshly `wr_alloc`'d buffer and returns it. Any buffer handed
+// back to the host for reading MUST come from here (or `wr_alloc` directly) so that a
+// later `wr_dealloc(ptr, len)` frees the exact allocation `wr_alloc` made.
+fn copy_into_guest_alloc(bytes: &[u8]) -> *mut u8 {
+    let out_ptr = wr_alloc(bytes.len());
     unsafe {
-        let ptr_info = format!("WTF");
-        println(ptr_info.as_ptr(), ptr_info.len());
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), out_ptr, bytes.len());
     }
+    out_ptr
+}
+
+// Generates the `extern "C"` export for a typed handler: decode the bincode-encoded
+// request out of the buffer the host wrote, run `$handler`, bincode-encode the
+// response into a freshly `wr_alloc`'d buffer, and hand the host back a packed
+// `(ptr << 32) | len` fat pointer to read it from.
+macro_rules! wr_entry {
+    ($name:ident, $handler:path) => {
+        #[no_mangle]
+        pub extern "C" fn $name(ptr: *const u8, len: usize) -> u64 {
+            panic_hook::init();
+            let input = unsafe { std::slice::from_raw_parts(ptr, len) };
+            let req = bincode::deserialize(input).expect("wr_entry: bad request");
+            let resp = $handler(req);
+            let encoded = bincode::serialize(&resp).expect("wr_entry: encode failed");
+
+            let out_ptr = copy_into_guest_alloc(&encoded);
+            ((out_ptr as u64) << 32) | (encoded.len() as u64)
+        }
+    };
+}
 
+#[derive(serde::Deserialize)]
+struct ReadBytesReq {
+    data: Vec<u8>,
+}
+
+#[derive(serde::Serialize)]
+struct ReadBytesResp {
+    info: String,
+}
+
+fn read_big_bytes_handler(req: ReadBytesReq) -> ReadBytesResp {
     unsafe {
-        let mut input_data = Vec::from_raw_parts(ptr, len, len);
-        let mut input_ptr = input_data.as_mut_ptr();
-        input_ptr[1] = 2;
-        let ptr_info = format!("slice info {:?} {:?} {:?}", input_ptr, input_data.len(), input_ptr[1]);
+        let ptr_info = format!("got {} bytes", req.data.len());
         println(ptr_info.as_ptr(), ptr_info.len());
-        output.0 = input_ptr.as_ptr();
-        output.1 = input_data.len();
-        let done = format!("all done!");
-        println(done.as_ptr(), done.len());
     }
 
-    // slice[1] = 2;
+    let info = format!("{:?}", req.data);
+
+    log::debug!("all done!");
 
+    ReadBytesResp { info }
 }
+
+wr_entry!(read_big_bytes, read_big_bytes_handler);
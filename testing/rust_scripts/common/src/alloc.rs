@@ -0,0 +1,36 @@
+use std::alloc::{self, Layout};
+use std::ptr;
+
+// `wr_alloc`/`wr_dealloc` must agree on the exact `Layout` for a given `size`, since
+// `GlobalAlloc::dealloc` is unsound if called with a layout that doesn't match the one
+// `alloc` was called with. Routing both through this one function makes that provable
+// instead of an undocumented assumption about allocator internals (e.g. a `Vec`'s
+// capacity happening to equal the size it was created with). `align = 1` is valid for
+// any `size`, so both sides are trivially identical.
+fn layout_for(size: usize) -> Layout {
+    Layout::from_size_align(size, 1).expect("wr_alloc: invalid size")
+}
+
+// Host allocates guest-side memory by calling `wr_alloc`, copies bytes in, calls the
+// real function, reads the result, then calls `wr_dealloc`. Guest functions must never
+// reconstruct and drop a buffer they did not allocate themselves -- doing so on a
+// host-owned pointer double-frees or leaks.
+#[no_mangle]
+pub extern "C" fn wr_alloc(size: usize) -> *mut u8 {
+    if size == 0 {
+        // `GlobalAlloc::alloc` forbids zero-size layouts; any non-null, well-aligned
+        // pointer is a valid "buffer" for a zero-length region.
+        return ptr::NonNull::<u8>::dangling().as_ptr();
+    }
+    unsafe { alloc::alloc(layout_for(size)) }
+}
+
+#[no_mangle]
+pub extern "C" fn wr_dealloc(ptr: *mut u8, size: usize) {
+    if size == 0 {
+        return;
+    }
+    unsafe {
+        alloc::dealloc(ptr, layout_for(size));
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::Once;
+
+extern {
+    fn host_log(level: u32, target_ptr: *const u8, target_len: usize, msg_ptr: *const u8, msg_len: usize);
+}
+
+struct HostLogger;
+
+impl log::Log for HostLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let target = record.target();
+        let msg = format!("{}", record.args());
+        unsafe {
+            host_log(
+                record.level() as u32,
+                target.as_ptr(),
+                target.len(),
+                msg.as_ptr(),
+                msg.len(),
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: HostLogger = HostLogger;
+static INIT: Once = Once::new();
+
+pub fn init() {
+    INIT.call_once(|| {
+        log::set_logger(&LOGGER).expect("host logger already installed");
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+}
+
+// Backward-compatible shim: old call sites did `println(ptr, len)` against the raw
+// `extern { fn println(...) }` import. Keep that shape but route it through the real
+// logger at info level instead of a bare host import.
+pub unsafe fn println(ptr: *const u8, len: usize) {
+    init();
+    let msg = std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr, len));
+    log::info!("{}", msg);
+}
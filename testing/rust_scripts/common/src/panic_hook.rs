@@ -0,0 +1,44 @@
+use std::panic;
+use std::sync::Once;
+
+use crate::host_logger;
+
+static INIT: Once = Once::new();
+
+// Installs the host logger and a panic hook that formats the guest panic (location +
+// message) and ships it to the host over the logging import before the trap
+// propagates, instead of the guest just aborting with nothing reaching the embedder.
+//
+// Idempotent and called lazily from every entry point (see `wr_entry!`) so a host that
+// never calls the exported `wr_init` still gets panics forwarded instead of an opaque
+// trap -- e.g. `bincode::deserialize(...).expect(...)` in `wr_entry!`. `wr_init` itself
+// just calls this, for a host that wants to pay the setup cost up front.
+pub fn init() {
+    INIT.call_once(|| {
+        host_logger::init();
+        panic::set_hook(Box::new(|info| {
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+                .unwrap_or_else(|| "<unknown location>".to_string());
+
+            let message = info
+                .payload()
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "<no message>".to_string());
+
+            log::error!("guest panicked at {}: {}", location, message);
+
+            unsafe {
+                core::arch::wasm32::unreachable();
+            }
+        }));
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn wr_init() {
+    init();
+}
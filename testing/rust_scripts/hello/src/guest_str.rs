@@ -0,0 +1,84 @@
+use std::cell::OnceCell;
+use std::char;
+use std::slice;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8 = 0,
+    Utf16Le = 1,
+}
+
+impl Encoding {
+    fn from_tag(tag: u8) -> Encoding {
+        match tag {
+            1 => Encoding::Utf16Le,
+            _ => Encoding::Utf8,
+        }
+    }
+}
+
+// A string coming across the boundary, tagged with its source encoding so we don't
+// assume every inbound byte slice is UTF-8 (editor/browser hosts commonly pass
+// UTF-16/UCS2). Decoding is lazy and cached the first time `as_utf8` is called.
+pub struct GuestStr {
+    encoding: Encoding,
+    raw: Vec<u8>,
+    decoded: OnceCell<String>,
+}
+
+impl GuestStr {
+    /// `ptr` points at a single encoding-tag byte followed by `len - 1` bytes of payload.
+    /// `len == 0` (no tag byte at all) is treated as an empty UTF-8 string.
+    pub unsafe fn from_raw(ptr: *const u8, len: usize) -> GuestStr {
+        if len == 0 {
+            return GuestStr {
+                encoding: Encoding::Utf8,
+                raw: Vec::new(),
+                decoded: OnceCell::new(),
+            };
+        }
+
+        let encoding = Encoding::from_tag(*ptr);
+        let raw = slice::from_raw_parts(ptr.add(1), len - 1).to_vec();
+        GuestStr {
+            encoding,
+            raw,
+            decoded: OnceCell::new(),
+        }
+    }
+
+    pub fn as_utf8(&self) -> &str {
+        self.decoded.get_or_init(|| match self.encoding {
+            Encoding::Utf8 => String::from_utf8_lossy(&self.raw).into_owned(),
+            Encoding::Utf16Le => {
+                let chunks = self.raw.chunks_exact(2);
+                let has_trailing_byte = !chunks.remainder().is_empty();
+                let units = chunks.map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+                let mut decoded: String = char::decode_utf16(units)
+                    .map(|r| r.unwrap_or('\u{FFFD}'))
+                    .collect();
+                // A dangling trailing byte means a malformed payload; substitute
+                // U+FFFD instead of silently dropping it.
+                if has_trailing_byte {
+                    decoded.push('\u{FFFD}');
+                }
+                decoded
+            }
+        })
+    }
+
+    /// Re-encodes `s` using this GuestStr's original encoding (tag byte + payload), so a
+    /// round trip through the host preserves the caller's wideness.
+    pub fn encode_for_return(&self, s: &str) -> Vec<u8> {
+        let mut out = vec![self.encoding as u8];
+        match self.encoding {
+            Encoding::Utf8 => out.extend_from_slice(s.as_bytes()),
+            Encoding::Utf16Le => {
+                for unit in s.encode_utf16() {
+                    out.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+        }
+        out
+    }
+}
@@ -1,12 +1,101 @@
 use std::slice;
-use std::str;
 
-extern {
-    fn println(ptr: *const u8, len: usize);
-}
+mod guest_str;
+mod rpc;
+
+// `host_logger`, `panic_hook` and `guest_alloc` are identical in the `hello` and
+// `big_bytes` plugin crates (there's no shared Cargo crate to hang them off), so they live
+// once under `common/` and each of those crates points its own module at that file instead of keeping
+// a byte-for-byte copy that would drift.
+#[path = "../../common/src/alloc.rs"]
+mod guest_alloc;
+#[path = "../../common/src/host_logger.rs"]
+mod host_logger;
+#[path = "../../common/src/panic_hook.rs"]
+mod panic_hook;
+
+use guest_alloc::wr_alloc;
+use guest_str::GuestStr;
+use host_logger::println;
 
 static HELLO: &'static str = "Hello, World!";
 
+// Copies `bytes` into a freshly `wr_alloc`'d buffer and returns it. Any buffer handed
+// back to the host for reading MUST come from here (or `wr_alloc` directly) so that a
+// later `wr_dealloc(ptr, len)` frees the exact allocation `wr_alloc` made.
+fn copy_into_guest_alloc(bytes: &[u8]) -> *mut u8 {
+    let out_ptr = wr_alloc(bytes.len());
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), out_ptr, bytes.len());
+    }
+    out_ptr
+}
+
+// Generates the `extern "C"` export for a typed handler: decode the bincode-encoded
+// request out of the buffer the host wrote, run `$handler`, bincode-encode the
+// response into a freshly `wr_alloc`'d buffer, and hand the host back a packed
+// `(ptr << 32) | len` fat pointer to read it from. Lets a plugin author just write
+// `fn handler(req: MyReq) -> MyResp` instead of hand-rolling the ptr/len boilerplate.
+macro_rules! wr_entry {
+    ($name:ident, $handler:path) => {
+        #[no_mangle]
+        pub extern "C" fn $name(ptr: *const u8, len: usize) -> u64 {
+            panic_hook::init();
+            let input = unsafe { slice::from_raw_parts(ptr, len) };
+            let req = bincode::deserialize(input).expect("wr_entry: bad request");
+            let resp = $handler(req);
+            let encoded = bincode::serialize(&resp).expect("wr_entry: encode failed");
+
+            let out_ptr = copy_into_guest_alloc(&encoded);
+            ((out_ptr as u64) << 32) | (encoded.len() as u64)
+        }
+    };
+}
+
+#[derive(serde::Deserialize)]
+struct GreetReq {
+    name: String,
+}
+
+#[derive(serde::Serialize)]
+struct GreetResp {
+    greeting: String,
+}
+
+fn greet_handler(req: GreetReq) -> GreetResp {
+    log::info!("greeting {:?}", req.name);
+
+    GreetResp {
+        greeting: format!("Hello {}, ca marche pontiac", req.name),
+    }
+}
+
+wr_entry!(greet, greet_handler);
+
+#[derive(serde::Deserialize)]
+struct LookupReq {
+    key: String,
+}
+
+#[derive(serde::Serialize)]
+struct LookupResp {
+    value: Option<String>,
+}
+
+// Demonstrates a guest plugin requesting work from the host (e.g. a KV lookup) via the
+// generic host-callback RPC, instead of the guest computing everything itself.
+fn lookup_handler(req: LookupReq) -> LookupResp {
+    match rpc::call::<_, String>("kv.get", &req.key) {
+        Ok(value) => LookupResp { value: Some(value) },
+        Err(err) => {
+            log::error!("kv.get failed: {:?}", err);
+            LookupResp { value: None }
+        }
+    }
+}
+
+wr_entry!(lookup, lookup_handler);
+
 #[repr(C)]
 pub struct Ptr {
     ptr: i32,
@@ -15,8 +104,12 @@ pub struct Ptr {
 
 #[no_mangle]
 pub extern "C" fn hello(ptr: *const u8, len: usize, output: &mut (*const u8, usize), output2: &mut (*const u8, usize) ) -> i32 {
-    let slice = unsafe { slice::from_raw_parts(ptr as _, len as _) };
-    let string_from_host = str::from_utf8(&slice).unwrap();
+    panic_hook::init();
+
+    // `ptr`/`len` cover a leading encoding-tag byte followed by the payload, so this no
+    // longer traps on a host that hands us UTF-16/UCS2 text.
+    let input = unsafe { GuestStr::from_raw(ptr, len) };
+    let string_from_host = input.as_utf8();
 
     unsafe {
         let ptr_info = format!("input ptr {:?} {:?}", ptr, len);
@@ -33,11 +126,13 @@ pub extern "C" fn hello(ptr: *const u8, len: usize, output: &mut (*const u8, usi
     }
 
     let from_within = format!("This {}, comes from within", string_from_host);
-    
-    output.0 = from_within.as_ptr();
+    let from_within = input.encode_for_return(&from_within);
+    let string_from_host = input.encode_for_return(string_from_host);
+
+    output.0 = copy_into_guest_alloc(&from_within);
     output.1 = from_within.len();
 
-    output2.0 = string_from_host.as_ptr();
+    output2.0 = copy_into_guest_alloc(&string_from_host);
     output2.1 = string_from_host.len();
 
     42
@@ -0,0 +1,85 @@
+use std::ptr;
+use std::slice;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::guest_alloc::{wr_alloc, wr_dealloc};
+
+extern {
+    // Guest serializes a method name plus bincode-encoded arguments; the host dispatches
+    // to a registered handler and writes a bincode-encoded result into `out`. Returns a
+    // status/error code (0 == success).
+    fn host_call(
+        method_ptr: *const u8,
+        method_len: usize,
+        args_ptr: *const u8,
+        args_len: usize,
+        out: &mut (*const u8, usize),
+    ) -> i32;
+
+    // `out` is host-owned memory handed to the guest only for the duration of the read;
+    // the guest must hand it back with `host_call_free` once it's done decoding, whether
+    // or not `host_call` reported success.
+    fn host_call_free(ptr: *const u8, len: usize);
+}
+
+#[derive(Debug)]
+pub enum HostError {
+    Status(i32),
+    Encode(bincode::Error),
+    Decode(bincode::Error),
+}
+
+// Typed wrapper around `host_call`: packs `req` into a `wr_alloc`'d buffer, invokes the
+// named host service, and decodes the bincode response.
+pub fn call<Req, Resp>(method: &str, req: &Req) -> Result<Resp, HostError>
+where
+    Req: Serialize,
+    Resp: DeserializeOwned,
+{
+    let encoded = bincode::serialize(req).map_err(HostError::Encode)?;
+
+    let arg_ptr = wr_alloc(encoded.len());
+    unsafe {
+        ptr::copy_nonoverlapping(encoded.as_ptr(), arg_ptr, encoded.len());
+    }
+
+    let mut out: (*const u8, usize) = (ptr::null(), 0);
+    let status = unsafe {
+        host_call(
+            method.as_ptr(),
+            method.len(),
+            arg_ptr,
+            encoded.len(),
+            &mut out,
+        )
+    };
+
+    wr_dealloc(arg_ptr, encoded.len());
+
+    // An empty `out` buffer is a genuine response (e.g. `Resp = ()`, which bincode
+    // encodes to zero bytes) whenever the call itself succeeded -- only fall back to the
+    // status code as the error when there was nothing to decode.
+    if out.0.is_null() || out.1 == 0 {
+        return if status != 0 {
+            Err(HostError::Status(status))
+        } else {
+            bincode::deserialize(&[]).map_err(HostError::Decode)
+        };
+    }
+
+    let result = {
+        let bytes = unsafe { slice::from_raw_parts(out.0, out.1) };
+        bincode::deserialize(bytes).map_err(HostError::Decode)
+    };
+    unsafe {
+        host_call_free(out.0, out.1);
+    }
+
+    if status != 0 {
+        return Err(HostError::Status(status));
+    }
+
+    result
+}